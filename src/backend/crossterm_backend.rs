@@ -0,0 +1,74 @@
+use crate::event::{self, Event, Key};
+use crossterm::event::{Event as CEvent, EventStream, KeyCode};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::tty::IsTty;
+use crossterm::{cursor, execute};
+use futures_util::stream::StreamExt;
+use std::io::{self, Write};
+use tui::backend::CrosstermBackend;
+use tui::Terminal;
+
+pub(crate) type Chosen = CrosstermBackend<io::Stdout>;
+
+pub(crate) fn is_input_a_tty() -> bool {
+    io::stdin().is_tty()
+}
+
+pub(crate) fn setup() -> io::Result<Terminal<Chosen>> {
+    enable_raw_mode().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let backend = CrosstermBackend::new(stdout);
+    Terminal::new(backend)
+}
+
+// Leaves the alternate screen, shows the cursor, and disables raw mode. Safe to call more
+// than once, and safe to call from a panic hook or a signal handler.
+pub(crate) fn restore() {
+    let mut stdout = io::stdout();
+    let _ = execute!(stdout, cursor::Show, LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+}
+
+// Feed key presses and terminal resizes into the shared event channel. crossterm's event
+// stream already interleaves both, so unlike the termion backend we don't need a separate
+// resize watcher.
+pub(crate) fn spawn_input(tx: event::Writer) -> io::Result<()> {
+    tokio::spawn(async move {
+        let mut events = EventStream::new();
+        while let Some(event) = events.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+            let sent = match event {
+                CEvent::Key(key) => match map_key(key.code) {
+                    Some(key) => tx.send(Event::Key(key)),
+                    None => continue,
+                },
+                CEvent::Resize(w, h) => tx.send(Event::Resize((w, h))),
+                CEvent::Mouse(_) => continue,
+            };
+            if sent.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn map_key(code: KeyCode) -> Option<Key> {
+    Some(match code {
+        KeyCode::Char(c) => Key::Char(c),
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Esc => Key::Esc,
+        _ => return None,
+    })
+}
@@ -0,0 +1,14 @@
+//! Terminal setup, the raw-mode/alternate-screen lifecycle, and the key + resize input
+//! producer, factored behind a small cfg-gated module per backend. Everything outside this
+//! module only ever sees `backend::Chosen` and `event::Event`, so `main` and `draw` stay
+//! backend-agnostic.
+
+#[cfg(not(feature = "crossterm"))]
+mod termion_backend;
+#[cfg(not(feature = "crossterm"))]
+pub(crate) use termion_backend::{is_input_a_tty, restore, setup, spawn_input, Chosen};
+
+#[cfg(feature = "crossterm")]
+mod crossterm_backend;
+#[cfg(feature = "crossterm")]
+pub(crate) use crossterm_backend::{is_input_a_tty, restore, setup, spawn_input, Chosen};
@@ -0,0 +1,103 @@
+use crate::event::{self, Event, Key};
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
+use std::sync::Mutex;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::AlternateScreen;
+use tui::backend::TermionBackend;
+use tui::Terminal;
+
+pub(crate) type Chosen = TermionBackend<AlternateScreen<RawTerminal<io::Stdout>>>;
+
+// The terminal's termios settings from before we put it in raw mode, so a panic hook or
+// Ctrl-C handler can put it back even if our `RawTerminal` guard never gets to run its
+// `Drop` (e.g. because the panic happened in a spawned task, or the process is signalled).
+static ORIGINAL_TERMIOS: Mutex<Option<termios::Termios>> = Mutex::new(None);
+
+pub(crate) fn is_input_a_tty() -> bool {
+    termion::is_tty(&io::stdin().lock())
+}
+
+pub(crate) fn setup() -> io::Result<Terminal<Chosen>> {
+    // stdin is the profiler pipe, so the terminal's settings live on stdout
+    *ORIGINAL_TERMIOS.lock().unwrap() = termios::Termios::from_fd(io::stdout().as_raw_fd()).ok();
+
+    let stdout = io::stdout().into_raw_mode()?;
+    let screen = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(screen);
+    Terminal::new(backend)
+}
+
+// Leaves the alternate screen, shows the cursor, and restores the terminal's original mode.
+// Safe to call more than once, and safe to call from a panic hook or a signal handler.
+pub(crate) fn restore() {
+    let mut stdout = io::stdout();
+    let _ = write!(
+        stdout,
+        "{}{}",
+        termion::cursor::Show,
+        termion::screen::ToMainScreen
+    );
+    let _ = stdout.flush();
+    if let Some(original) = ORIGINAL_TERMIOS.lock().unwrap().take() {
+        let _ = termios::tcsetattr(io::stdout().as_raw_fd(), termios::TCSANOW, &original);
+    }
+}
+
+// Feed key presses and terminal resizes into the shared event channel.
+pub(crate) fn spawn_input(tx: event::Writer) -> io::Result<()> {
+    // a _super_ hacky way for us to get input from the TTY
+    let tty = termion::get_tty()?;
+    {
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            use termion::input::TermRead;
+            for key in tty.keys() {
+                let key = match key {
+                    Ok(key) => key,
+                    Err(_) => return,
+                };
+                let key = match map_key(key) {
+                    Some(key) => key,
+                    None => continue,
+                };
+                if tx.send(Event::Key(key)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    tokio::spawn(async move {
+        let mut resized =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::window_change()) {
+                Ok(resized) => resized,
+                Err(_) => return,
+            };
+        while resized.recv().await.is_some() {
+            let size = match termion::terminal_size() {
+                Ok(size) => size,
+                Err(_) => continue,
+            };
+            if tx.send(Event::Resize(size)).is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn map_key(key: termion::event::Key) -> Option<Key> {
+    use termion::event::Key as TKey;
+    Some(match key {
+        TKey::Char('\n') => Key::Enter,
+        TKey::Char(c) => Key::Char(c),
+        TKey::Up => Key::Up,
+        TKey::Down => Key::Down,
+        TKey::PageUp => Key::PageUp,
+        TKey::PageDown => Key::PageDown,
+        TKey::Esc => Key::Esc,
+        _ => return None,
+    })
+}
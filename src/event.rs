@@ -0,0 +1,65 @@
+//! A single stream of everything the UI needs to react to: input lines, key presses, terminal
+//! resizes, and redraw ticks. Keeping these on one channel means the main loop never misses a
+//! resize or stalls waiting on stdin -- it just reacts to whatever `Event` comes in next.
+
+/// A key press, abstracted over the handful of keys we care about so that the aggregation
+/// and drawing code never has to know whether it's running on the termion or crossterm
+/// backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Key {
+    Char(char),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Enter,
+    Esc,
+}
+
+/// A single parsed profiler sample: the frame-folded stack for `tid` observed at `time`.
+/// Recorded to and loaded from `--record`/`--load` trace files with serde.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Sample {
+    pub(crate) time: usize,
+    pub(crate) tid: usize,
+    pub(crate) stack: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Event {
+    /// A line of profiler input.
+    Line(String),
+    /// A sample loaded straight from a `--load`ed trace file, bypassing the text parser.
+    Sample(Sample),
+    /// A key was pressed on the controlling TTY.
+    Key(Key),
+    /// The terminal was resized to (cols, rows).
+    Resize((u16, u16)),
+    /// Time to redraw.
+    Tick,
+}
+
+/// The sending half of an [`Event`] channel. Cheaply `Clone`-able so each producer (stdin,
+/// keys, resize, tick) can hold its own handle.
+#[derive(Clone)]
+pub(crate) struct Writer(tokio::sync::mpsc::UnboundedSender<Event>);
+
+impl Writer {
+    pub(crate) fn send(&self, event: Event) -> Result<(), ()> {
+        self.0.send(event).map_err(|_| ())
+    }
+}
+
+/// The receiving half of an [`Event`] channel.
+pub(crate) struct Reader(tokio::sync::mpsc::UnboundedReceiver<Event>);
+
+impl Reader {
+    pub(crate) async fn recv(&mut self) -> Option<Event> {
+        self.0.recv().await
+    }
+}
+
+pub(crate) fn channel() -> (Writer, Reader) {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    (Writer(tx), Reader(rx))
+}
@@ -1,17 +1,22 @@
-use futures_util::future::Either;
+use event::{Event, Key, Sample};
 use futures_util::stream::StreamExt;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 use std::io::{self};
+use std::path::PathBuf;
 use structopt::StructOpt;
-use termion::raw::IntoRawMode;
 use tokio::prelude::*;
 use tui::backend::Backend;
-use tui::backend::TermionBackend;
-use tui::layout::{Constraint, Direction, Layout};
+use tui::buffer::Buffer;
+use tui::layout::{Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Modifier, Style};
 use tui::widgets::{Block, Borders, Paragraph, Text, Widget};
 use tui::Terminal;
 
+mod backend;
+mod event;
+mod record;
+
 const DRAW_EVERY: std::time::Duration = std::time::Duration::from_millis(200);
 const WINDOW: std::time::Duration = std::time::Duration::from_secs(10);
 
@@ -24,6 +29,15 @@ struct Opt {
     /// Treat input as a replay of a trace and emulate time accordingly.
     #[structopt(long)]
     replay: bool,
+
+    /// Record each parsed sample to this file as it's ingested, for later `--load`ing.
+    #[structopt(long)]
+    record: Option<PathBuf>,
+
+    /// Load a trace previously captured with `--record` instead of reading bpftrace text
+    /// from stdin.
+    #[structopt(long)]
+    load: Option<PathBuf>,
 }
 
 #[derive(Debug, Default)]
@@ -31,17 +45,47 @@ struct Thread {
     window: BTreeMap<usize, String>,
 }
 
+// Replay-only playback controls: Space pauses/resumes, `+`/`-` change the speed multiplier
+// applied to the emulated inter-frame delay, and `.` single-steps one frame while paused.
+#[derive(Debug)]
+struct Replay {
+    paused: bool,
+    speed: f64,
+    step: bool,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Replay {
+            paused: false,
+            speed: 1.0,
+            step: false,
+        }
+    }
+}
+
 fn main() -> Result<(), io::Error> {
     let opt = Opt::from_args();
 
-    if termion::is_tty(&io::stdin().lock()) {
+    if opt.load.is_none() && backend::is_input_a_tty() {
         eprintln!("Don't type input to this program, that's silly.");
         return Ok(());
     }
 
-    let stdout = io::stdout().into_raw_mode()?;
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let mut recorder = match &opt.record {
+        Some(path) => Some(record::Recorder::create(path)?),
+        None => None,
+    };
+    let replaying = opt.replay || opt.load.is_some();
+
+    install_panic_hook();
+    ctrlc::set_handler(|| {
+        backend::restore();
+        std::process::exit(130);
+    })
+    .expect("failed to install Ctrl-C handler");
+
+    let mut terminal: Terminal<backend::Chosen> = backend::setup()?;
 
     let mut tids = BTreeMap::new();
     let mut inframe = None;
@@ -62,31 +106,106 @@ fn main() -> Result<(), io::Error> {
             .render(&mut f, chunks[0]);
     })?;
 
-    // a _super_ hacky way for us to get input from the TTY
-    let tty = termion::get_tty()?;
-    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-    std::thread::spawn(move || {
-        use termion::input::TermRead;
-        for key in tty.keys() {
-            if let Err(_) = tx.send(key) {
-                return;
-            }
-        }
-    });
+    let (tx, mut rx) = event::channel();
 
     let mut rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async move {
-        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
-        let lines = stdin.lines().map(Either::Left);
-        let rx = rx.map(Either::Right);
-        let mut input = futures_util::stream::select(lines, rx);
+        // `spawn_input` calls tokio::spawn internally, so it needs to run from inside
+        // `block_on` rather than before the runtime exists
+        backend::spawn_input(tx.clone())?;
+        if let Some(path) = &opt.load {
+            record::spawn_loader(path, tx.clone())?;
+        }
+
+        // feed us lines of profiler input as they arrive, unless we're replaying a
+        // previously `--record`ed trace instead
+        if opt.load.is_none() {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+                let mut lines = stdin.lines();
+                while let Some(line) = lines.next().await {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => return,
+                    };
+                    if tx.send(Event::Line(line)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
 
-        let mut lastprint = 0;
+        // make sure we redraw regularly even if no new samples come in
+        {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(DRAW_EVERY);
+                loop {
+                    interval.tick().await;
+                    if tx.send(Event::Tick).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        // input that arrived while replay was paused, waiting to be fed through once we
+        // resume (or are single-stepped)
+        let mut pending: std::collections::VecDeque<Pending> = Default::default();
+        let mut replay = Replay::default();
+        let mut selection = Selection::default();
+        let mut current_tree = FlameNode::default();
         let mut lasttime = 0;
-        while let Some(got) = input.next().await {
-            match got {
-                Either::Left(line) => {
-                    let line = line.unwrap();
+        'events: loop {
+            // while replay is paused, keep draining the channel (so resizes still redraw
+            // and new samples aren't lost) without advancing the replay itself
+            while replaying && replay.paused && !replay.step {
+                match rx.recv().await {
+                    Some(Event::Line(line)) => pending.push_back(Pending::Line(line)),
+                    Some(Event::Sample(sample)) => pending.push_back(Pending::Sample(sample)),
+                    Some(Event::Key(key)) => {
+                        if !handle_key(replaying, &mut replay, &mut selection, &current_tree, key) {
+                            break 'events;
+                        }
+                        current_tree = draw(&mut terminal, &mut tids, &replay, &mut selection)?;
+                    }
+                    Some(Event::Resize(_)) | Some(Event::Tick) => {
+                        current_tree = draw(&mut terminal, &mut tids, &replay, &mut selection)?;
+                    }
+                    None => break 'events,
+                }
+            }
+
+            let input = if let Some(input) = pending.pop_front() {
+                input
+            } else {
+                match rx.recv().await {
+                    Some(Event::Line(line)) => Pending::Line(line),
+                    Some(Event::Sample(sample)) => Pending::Sample(sample),
+                    Some(Event::Key(key)) => {
+                        if !handle_key(replaying, &mut replay, &mut selection, &current_tree, key) {
+                            break 'events;
+                        }
+                        current_tree = draw(&mut terminal, &mut tids, &replay, &mut selection)?;
+                        continue;
+                    }
+                    Some(Event::Resize(_)) | Some(Event::Tick) => {
+                        current_tree = draw(&mut terminal, &mut tids, &replay, &mut selection)?;
+                        continue;
+                    }
+                    None => break 'events,
+                }
+            };
+
+            match input {
+                Pending::Sample(sample) => {
+                    let time = sample.time;
+                    ingest_sample(&mut tids, sample, &mut recorder);
+                    pace_replay(replaying, &mut lasttime, time, &mut replay).await;
+                }
+                Pending::Line(line) => {
                     if line.starts_with("Error") || line.starts_with("Attaching") {
                     } else if !line.starts_with(' ') || line.is_empty() {
                         if let Some((time, tid)) = inframe {
@@ -100,24 +219,12 @@ fn main() -> Result<(), io::Error> {
                                 let stackn = stack.len();
                                 stack.truncate(stackn - 1);
 
-                                tids.entry(tid)
-                                    .or_insert_with(Thread::default)
-                                    .window
-                                    .insert(time, stack);
-
-                                if opt.replay && lasttime != 0 && time - lasttime > 1_000_000 {
-                                    tokio::time::delay_for(std::time::Duration::from_nanos(
-                                        (time - lasttime) as u64,
-                                    ))
-                                    .await;
-                                }
-                                lasttime = time;
-                                if std::time::Duration::from_nanos((time - lastprint) as u64)
-                                    > DRAW_EVERY
-                                {
-                                    draw(&mut terminal, &mut tids)?;
-                                    lastprint = time;
-                                }
+                                ingest_sample(
+                                    &mut tids,
+                                    Sample { time, tid, stack },
+                                    &mut recorder,
+                                );
+                                pace_replay(replaying, &mut lasttime, time, &mut replay).await;
                             }
                             inframe = None;
                         }
@@ -143,12 +250,6 @@ fn main() -> Result<(), io::Error> {
                         stack.push(';');
                     }
                 }
-                Either::Right(key) => {
-                    let key = key?;
-                    if let termion::event::Key::Char('q') = key {
-                        break;
-                    }
-                }
             }
         }
 
@@ -157,10 +258,87 @@ fn main() -> Result<(), io::Error> {
     })
 }
 
+// Input queued up while replay was paused: either a raw line still waiting to be parsed, or
+// a sample loaded straight from a `--load`ed trace.
+enum Pending {
+    Line(String),
+    Sample(Sample),
+}
+
+// Record (if `--record`ing) and store a finished sample.
+fn ingest_sample(
+    tids: &mut BTreeMap<usize, Thread>,
+    sample: Sample,
+    recorder: &mut Option<record::Recorder>,
+) {
+    if let Some(recorder) = recorder {
+        let _ = recorder.write(&sample);
+    }
+    tids.entry(sample.tid)
+        .or_insert_with(Thread::default)
+        .window
+        .insert(sample.time, sample.stack);
+}
+
+// Emulate the wall-clock pacing between samples when replaying a trace, honoring the
+// current pause/speed/step state.
+async fn pace_replay(replaying: bool, lasttime: &mut usize, time: usize, replay: &mut Replay) {
+    if replaying && !replay.step && *lasttime != 0 && time - *lasttime > 1_000_000 {
+        let nanos = (time - *lasttime) as f64 / replay.speed;
+        tokio::time::delay_for(std::time::Duration::from_nanos(nanos as u64)).await;
+    }
+    *lasttime = time;
+
+    // we've now taken our one step
+    replay.step = false;
+}
+
+// Handle a key press that isn't tied to a specific pending line: playback controls when
+// replaying, and flamegraph navigation the rest of the time. Returns `false` if the program
+// should quit. `tree` is the full (unzoomed) tree as of the last redraw, which is all
+// `selection`'s navigation needs to figure out siblings and children.
+fn handle_key(
+    replaying: bool,
+    replay: &mut Replay,
+    selection: &mut Selection,
+    tree: &FlameNode,
+    key: Key,
+) -> bool {
+    match key {
+        Key::Char('q') => return false,
+        Key::Char(' ') if replaying => replay.paused = !replay.paused,
+        Key::Char('+') if replaying => replay.speed *= 2.0,
+        Key::Char('-') if replaying => replay.speed /= 2.0,
+        Key::Char('.') if replaying && replay.paused => replay.step = true,
+        Key::Up | Key::Char('k') => selection.select_sibling(tree, false),
+        Key::Down | Key::Char('j') => selection.select_sibling(tree, true),
+        Key::PageDown => selection.descend(tree),
+        Key::PageUp => selection.ascend(),
+        Key::Enter => selection.zoom_in(),
+        Key::Esc => selection.zoom_out(),
+        _ => {}
+    }
+    true
+}
+
+// Make sure a panic mid-parse (e.g. the `expect("invalid tid")` paths) doesn't leave the
+// user's shell stuck in raw mode on the alternate screen with the cursor hidden.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        backend::restore();
+        default_hook(info);
+    }));
+}
+
+// Draws the flamegraph and returns the full (unzoomed) tree it was built from, so the caller
+// can hang onto it for `selection`'s navigation to consult between redraws.
 fn draw<B: Backend>(
     terminal: &mut Terminal<B>,
     threads: &mut BTreeMap<usize, Thread>,
-) -> Result<(), io::Error> {
+    replay: &Replay,
+    selection: &mut Selection,
+) -> Result<FlameNode, io::Error> {
     // keep our window relatively short
     let mut latest = 0;
     for thread in threads.values() {
@@ -180,113 +358,307 @@ fn draw<B: Backend>(
     // now only reading
     let threads = &*threads;
 
-    let mut lines = Vec::new();
-    let mut hits = HashMap::new();
-    let mut maxes = BTreeMap::new();
-    for (_, thread) in threads {
-        // add up across the window
-        let mut max: Option<(&str, usize)> = None;
-        for (&time, stack) in &thread.window {
-            latest = std::cmp::max(latest, time);
-            let mut at = stack.len();
-            while let Some(stack_start) = stack[..at].rfind(';') {
-                at = stack_start;
-                let stack = &stack[at + 1..];
-                let count = hits.entry(stack).or_insert(0);
-                *count += 1;
-                if let Some((_, max_count)) = max {
-                    if *count >= max_count {
-                        max = Some((stack, *count));
-                    }
-                } else {
-                    max = Some((stack, *count));
+    let root = build_flamegraph(threads);
+    if root.count == 0 {
+        return Ok(root);
+    }
+
+    // drop any selection/zoom that no longer matches the tree, e.g. because the frame aged
+    // out of the window
+    selection.clamp(&root);
+    let zoomed = find_node(&root, &selection.zoom).unwrap_or(&root);
+
+    let title = if replay.paused {
+        "Common thread fan-out points [paused]".to_string()
+    } else if (replay.speed - 1.0).abs() > f64::EPSILON {
+        format!("Common thread fan-out points [{:.2}x]", replay.speed)
+    } else {
+        "Common thread fan-out points".to_string()
+    };
+
+    // when a node is pinned (zoomed into), show the full ancestor stack leading to it, not
+    // just the pinned node's own name -- the flamegraph below it only has room for its
+    // descendants once it's re-rooted there
+    let ancestor_stack = if selection.zoom.is_empty() {
+        None
+    } else {
+        Some(
+            selection
+                .zoom
+                .iter()
+                .map(|frame| format!("{}", rustc_demangle::demangle(frame)))
+                .collect::<Vec<_>>()
+                .join(" -> "),
+        )
+    };
+
+    let selected = selection.path.clone();
+    terminal.draw(|mut f| {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .split(f.size());
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(&title)
+            .title_style(Style::default().fg(Color::Magenta).modifier(Modifier::BOLD));
+        let inner = block.inner(chunks[0]);
+        block.render(&mut f, chunks[0]);
+
+        let graph_area = if let Some(stack) = &ancestor_stack {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                .split(inner);
+            Paragraph::new([Text::raw(stack)].iter())
+                .style(Style::default().fg(Color::Yellow))
+                .render(&mut f, rows[0]);
+            rows[1]
+        } else {
+            inner
+        };
+
+        FlameGraph {
+            root: zoomed,
+            selected: &selected,
+        }
+        .render(&mut f, graph_area);
+    })?;
+
+    Ok(root)
+}
+
+// A node in the prefix tree built from the `;`-joined folded stacks in each thread's window.
+// Each node is keyed (in its parent) by frame name, and `count` is the number of samples in
+// the current WINDOW, summed across every thread, that passed through this frame.
+#[derive(Debug, Default)]
+struct FlameNode {
+    children: BTreeMap<String, FlameNode>,
+    count: usize,
+}
+
+impl FlameNode {
+    fn insert(&mut self, frames: &[&str]) {
+        self.count += 1;
+        if let Some((frame, rest)) = frames.split_first() {
+            self.children
+                .entry((*frame).to_string())
+                .or_insert_with(FlameNode::default)
+                .insert(rest);
+        }
+    }
+}
+
+// Navigation state for the flamegraph. `path` is the currently highlighted node, given as a
+// sequence of frame names measured from the zoom root; `zoom`, when non-empty, re-roots the
+// rendered graph at that node so a deep or narrow stack can be drilled into. Both are relative
+// to the full tree built on the most recent redraw -- see `clamp`.
+//
+// This adapts a request that was originally written against the flat fan-out `Text` list:
+// that list was replaced by the flamegraph widget itself in an earlier change, so there's no
+// scrollable line list left to add a cursor/offset to. Up/Down/j/k/PageUp/PageDown walk the
+// flamegraph's siblings and children instead of scrolling lines, and Enter "pins" the selected
+// node the same way the original request asked -- the view re-roots there (`zoom`) and `draw`
+// renders the full ancestor stack leading to it above the graph, rather than just its name.
+// Esc unpins one level at a time.
+#[derive(Debug, Default)]
+struct Selection {
+    path: Vec<String>,
+    zoom: Vec<String>,
+}
+
+impl Selection {
+    // Drop any trailing `path`/`zoom` components that no longer resolve in `root`, e.g.
+    // because the frame they name aged out of the window.
+    fn clamp(&mut self, root: &FlameNode) {
+        truncate_to_valid(&mut self.zoom, root);
+        let zoomed = find_node(root, &self.zoom).unwrap_or(root);
+        truncate_to_valid(&mut self.path, zoomed);
+    }
+
+    // Move the selection to the previous (`forward == false`) or next sibling of the
+    // currently selected node. If nothing is selected yet, selects the heaviest top-level
+    // child instead.
+    fn select_sibling(&mut self, root: &FlameNode, forward: bool) {
+        let zoomed = match find_node(root, &self.zoom) {
+            Some(zoomed) => zoomed,
+            None => return,
+        };
+
+        let (parent_path, current) = match self.path.split_last() {
+            Some((current, parent_path)) => (parent_path.to_vec(), current.clone()),
+            None => {
+                if let Some(frame) = heaviest_child(zoomed) {
+                    self.path = vec![frame];
                 }
+                return;
             }
+        };
+        let parent = find_node(zoomed, &parent_path).unwrap_or(zoomed);
+        let siblings: Vec<&String> = parent.children.keys().collect();
+        if let Some(pos) = siblings.iter().position(|&frame| *frame == current) {
+            let next = if forward {
+                (pos + 1).min(siblings.len() - 1)
+            } else {
+                pos.saturating_sub(1)
+            };
+            let mut path = parent_path;
+            path.push(siblings[next].clone());
+            self.path = path;
         }
+    }
 
-        if let Some((stack, count)) = max {
-            let e = maxes.entry(stack).or_insert((0, 0));
-            e.0 += 1;
-            e.1 += count;
+    // Select the heaviest child of the currently selected node.
+    fn descend(&mut self, root: &FlameNode) {
+        let zoomed = match find_node(root, &self.zoom) {
+            Some(zoomed) => zoomed,
+            None => return,
+        };
+        let current = find_node(zoomed, &self.path).unwrap_or(zoomed);
+        if let Some(frame) = heaviest_child(current) {
+            self.path.push(frame);
         }
-        hits.clear();
     }
 
-    if maxes.is_empty() {
-        return Ok(());
+    // Select the parent of the currently selected node.
+    fn ascend(&mut self) {
+        self.path.pop();
     }
 
-    let max = *maxes.values().map(|(_, count)| count).max().unwrap() as f64;
-
-    // sort by where most threads are
-    let mut maxes: Vec<_> = maxes.into_iter().collect();
-    maxes.sort_by_key(|(_, (nthreads, _))| *nthreads);
+    // Re-root the graph at the currently selected node.
+    fn zoom_in(&mut self) {
+        if !self.path.is_empty() {
+            self.zoom.append(&mut self.path);
+        }
+    }
 
-    for (stack, (nthreads, count)) in maxes.iter().rev() {
-        let count = *count;
-        let nthreads = *nthreads;
+    // Zoom out one level, or clear the selection if we're already at the top.
+    fn zoom_out(&mut self) {
+        match self.zoom.pop() {
+            // keep `path` measured from the new zoom root
+            Some(frame) => self.path.insert(0, frame),
+            None => self.path.clear(),
+        }
+    }
+}
 
-        if stack.find(';').is_none() {
-            // this thread just shares the root frame
-            continue;
+// Truncate `path` at the first component that doesn't resolve in `root`.
+fn truncate_to_valid(path: &mut Vec<String>, root: &FlameNode) {
+    let mut node = root;
+    let mut valid = 0;
+    for frame in path.iter() {
+        match node.children.get(frame) {
+            Some(child) => {
+                node = child;
+                valid += 1;
+            }
+            None => break,
         }
+    }
+    path.truncate(valid);
+}
+
+// Walk `path` from `root`, following one child per frame name.
+fn find_node<'a>(root: &'a FlameNode, path: &[String]) -> Option<&'a FlameNode> {
+    let mut node = root;
+    for frame in path {
+        node = node.children.get(frame)?;
+    }
+    Some(node)
+}
 
-        if count == 1 {
-            // this thread only has one sample ever, let's reduce noise...
-            continue;
+fn heaviest_child(node: &FlameNode) -> Option<String> {
+    node.children
+        .iter()
+        .max_by_key(|(_, child)| child.count)
+        .map(|(frame, _)| frame.clone())
+}
+
+fn build_flamegraph(threads: &BTreeMap<usize, Thread>) -> FlameNode {
+    let mut root = FlameNode::default();
+    for thread in threads.values() {
+        for stack in thread.window.values() {
+            let frames: Vec<&str> = stack.split(';').collect();
+            root.insert(&frames);
         }
+    }
+    root
+}
 
-        let red = (128.0 * count as f64 / max) as u8;
-        let color = Color::Rgb(255, 128 - red, 128 - red);
+// Derive a stable background color for a frame so the same function always gets the same
+// color across redraws, the way flamegraph.pl's palette works. Hashed on the demangled name,
+// since that's what's actually shown, rather than the raw (mangled) symbol.
+fn frame_color(frame: &str) -> Color {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    rustc_demangle::demangle(frame)
+        .to_string()
+        .hash(&mut hasher);
+    let h = hasher.finish();
+    Color::Rgb(
+        128 + (h & 0x7f) as u8,
+        128 + ((h >> 8) & 0x7f) as u8,
+        128 + ((h >> 16) & 0x7f) as u8,
+    )
+}
 
-        if nthreads == 1 {
-            lines.push(Text::styled(
-                format!("A thread fanned out from here {} times\n", count),
-                Style::default().modifier(Modifier::BOLD).fg(color),
-            ));
-        } else {
-            lines.push(Text::styled(
-                format!(
-                    "{} threads fanned out from here {} times\n",
-                    nthreads, count
-                ),
-                Style::default().modifier(Modifier::BOLD).fg(color),
-            ));
+// Renders `root` icicle-style: the root occupies the full width of the area, and at each
+// depth children are laid out left-to-right with a width proportional to their share of
+// their parent's sample count. Children that would be narrower than one cell are dropped.
+// The node at `selected` (a path of frame names from `root`), if any, is drawn with a
+// distinct style so `Selection`'s navigation has something to show for itself.
+struct FlameGraph<'a> {
+    root: &'a FlameNode,
+    selected: &'a [String],
+}
+
+impl<'a> Widget for FlameGraph<'a> {
+    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        self.draw_node(self.root, &[], area, buf);
+    }
+}
+
+impl<'a> FlameGraph<'a> {
+    fn draw_node(&self, node: &FlameNode, path: &[String], area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 || node.count == 0 {
+            return;
         }
 
-        for (i, frame) in stack.split(';').enumerate() {
-            // https://github.com/alexcrichton/rustc-demangle/issues/34
-            if i == 0 {
-                lines.push(Text::styled(
-                    format!("  {}\n", rustc_demangle::demangle(frame)),
-                    Style::default(),
-                ));
+        let y = area.top();
+        let mut x = area.left();
+        for (frame, child) in &node.children {
+            let width = (child.count as f64 / node.count as f64 * f64::from(area.width)) as u16;
+            if width == 0 {
+                // narrower than one cell, drop it
+                continue;
+            }
+
+            let mut child_path = path.to_vec();
+            child_path.push(frame.clone());
+
+            let color = frame_color(frame);
+            let style = if child_path == self.selected {
+                Style::default()
+                    .fg(Color::White)
+                    .bg(color)
+                    .modifier(Modifier::BOLD)
             } else {
-                lines.push(Text::styled(
-                    format!("  {}\n", rustc_demangle::demangle(frame)),
-                    Style::default().modifier(Modifier::DIM),
-                ));
+                Style::default().fg(Color::Black).bg(color)
+            };
+            for dx in 0..width {
+                buf.get_mut(x + dx, y).set_bg(color);
             }
-        }
-        lines.push(Text::raw("\n"));
-    }
 
-    terminal.draw(|mut f| {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(2)
-            .constraints([Constraint::Percentage(100)].as_ref())
-            .split(f.size());
+            let label = format!("{}", rustc_demangle::demangle(frame));
+            buf.set_stringn(x, y, &label, width as usize, style);
 
-        Paragraph::new(lines.iter())
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Common thread fan-out points")
-                    .title_style(Style::default().fg(Color::Magenta).modifier(Modifier::BOLD)),
-            )
-            .render(&mut f, chunks[0]);
-    })?;
+            if area.height > 1 {
+                let child_area = Rect::new(x, y + 1, width, area.height - 1);
+                self.draw_node(child, &child_path, child_area, buf);
+            }
 
-    Ok(())
+            x += width;
+        }
+    }
 }
@@ -0,0 +1,37 @@
+//! On-disk trace capture/playback. `--record` serializes each [`Sample`] as it's parsed from
+//! bpftrace's text format; `--load` deserializes them straight back into `Event::Sample`,
+//! skipping the text parser entirely. Traces are just a concatenation of bincode-encoded
+//! `Sample`s, read back one value at a time until EOF.
+
+use crate::event::{self, Event, Sample};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+pub(crate) struct Recorder(BufWriter<File>);
+
+impl Recorder {
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        Ok(Recorder(BufWriter::new(File::create(path)?)))
+    }
+
+    pub(crate) fn write(&mut self, sample: &Sample) -> io::Result<()> {
+        bincode::serialize_into(&mut self.0, sample)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+// Feed a previously `--record`ed trace straight into the event loop.
+pub(crate) fn spawn_loader(path: &Path, tx: event::Writer) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+    std::thread::spawn(move || loop {
+        let sample: Sample = match bincode::deserialize_from(&mut reader) {
+            Ok(sample) => sample,
+            Err(_) => return,
+        };
+        if tx.send(Event::Sample(sample)).is_err() {
+            return;
+        }
+    });
+    Ok(())
+}